@@ -0,0 +1,239 @@
+use std::net::{IpAddr, SocketAddr};
+
+use futures::future::join_all;
+use tokio::net::{TcpStream as TokioTcpStream, UdpSocket};
+use tokio::runtime::Runtime;
+use trust_dns_client::client::{AsyncClient, Client, ClientHandle, SecureSyncClient};
+use trust_dns_client::op::DnsResponse;
+use trust_dns_client::rr::{DNSClass, RData, Record};
+use trust_dns_client::tcp::TcpClientStream;
+use trust_dns_client::udp::{UdpClientConnection, UdpClientStream};
+
+use crate::{Check, CheckFailure, ExpectedRecord, Transport};
+
+const RETRIES: usize = 2;
+
+/// Runs a batch of `Check`s and reports a pass/fail per check, in the same order they
+/// were given. Behind a trait so a full sweep can be driven by the real DNS resolvers
+/// in `main` but swapped for a canned set of results in tests.
+pub trait Checker {
+    fn run_all(&self, checks: &[Check]) -> Vec<Result<(), CheckFailure>>;
+}
+
+/// `Checker` backed by the real DNS resolvers, via the async trust-dns client. Owns the
+/// `Runtime` the async client needs to drive its background task, created once here -
+/// the way a constructor would - rather than ad hoc on every check like the old
+/// `SyncClient`/`UdpClientConnection` pair in `perform_check` used to.
+pub struct DnsChecker {
+    runtime: Runtime,
+}
+
+impl DnsChecker {
+    pub fn new() -> std::io::Result<Self> {
+        Ok(Self {
+            runtime: Runtime::new()?,
+        })
+    }
+}
+
+impl Checker for DnsChecker {
+    /// Issues every check's query concurrently rather than one at a time, so a full
+    /// sweep of many servers/records takes roughly one round-trip instead of the sum
+    /// of all of them.
+    fn run_all(&self, checks: &[Check]) -> Vec<Result<(), CheckFailure>> {
+        self.runtime
+            .block_on(async { join_all(checks.iter().map(perform_check)).await })
+    }
+}
+
+async fn perform_check(check: &Check) -> Result<(), CheckFailure> {
+    let response = if check.dnssec {
+        // The async client doesn't have a DNSSEC-validating counterpart yet, so the
+        // secure query still goes through the blocking `SecureSyncClient`. Running it
+        // via `spawn_blocking` keeps it from stalling the rest of the concurrent sweep
+        // while it waits on its own socket.
+        let check_id = check.id();
+        let addr = check.name_server.address;
+        let record = check.record_to_request.clone();
+        let record_type = check.expected.record_type();
+        tokio::task::spawn_blocking(move || perform_secure_query(addr, &record, record_type))
+            .await
+            .unwrap_or_else(|_| {
+                panic!("secure query task for {} panicked", check_id);
+            })?
+    } else {
+        let mut client = connect_client(check).await?;
+        perform_query_with_retries(&mut client, check, RETRIES).await?
+    };
+
+    let answers: &[Record] = response.answers();
+
+    if answers.is_empty() {
+        return Err(CheckFailure::Resolution);
+    }
+
+    // A zone routinely returns more than one record for a name (e.g. several MX hosts,
+    // multiple TXT records) - check every answer for a match rather than only the
+    // first, or an expected record further down the response reads as a mismatch.
+    let matched = answers.iter().any(|answer| match (&check.expected, answer.rdata()) {
+        (ExpectedRecord::A(expected), RData::A(ref ip)) => ip == expected,
+        (ExpectedRecord::Aaaa(expected), RData::AAAA(ref ip)) => ip == expected,
+        (ExpectedRecord::Cname(expected), RData::CNAME(ref target)) => target == expected,
+        (ExpectedRecord::Mx(expected), RData::MX(ref mx)) => mx.exchange() == expected,
+        (ExpectedRecord::Txt(expected), RData::TXT(ref txt)) => txt
+            .txt_data()
+            .iter()
+            .any(|chunk| String::from_utf8_lossy(chunk).contains(expected.as_str())),
+        _ => false,
+    });
+
+    if matched {
+        Ok(())
+    } else {
+        Err(CheckFailure::Mismatch)
+    }
+}
+
+/// Opens an `AsyncClient` over whichever transport `check`'s nameserver is configured
+/// for. The resulting client is a plain `AsyncClient` regardless of transport - only
+/// the stream underneath differs - so `perform_query_with_retries` doesn't need to care
+/// which one it's talking to.
+async fn connect_client(check: &Check) -> Result<AsyncClient, CheckFailure> {
+    let transport = check.name_server.transport;
+    let socket_addr = SocketAddr::new(IpAddr::V4(check.name_server.address), transport.port());
+
+    let (client, background) = match transport {
+        Transport::Udp => {
+            let stream = UdpClientStream::<UdpSocket>::new(socket_addr);
+            AsyncClient::connect(stream)
+                .await
+                .map_err(|_| CheckFailure::Resolution)?
+        }
+        Transport::Tcp => {
+            let (stream, sender) = TcpClientStream::<TokioTcpStream>::new(socket_addr);
+            AsyncClient::new(stream, sender, None)
+                .await
+                .map_err(|_| CheckFailure::Resolution)?
+        }
+        Transport::Tls => {
+            let dns_name = check.name_server.tls_dns_name();
+            let (stream, sender) =
+                trust_dns_rustls::tls_client_connect::<TokioTcpStream>(socket_addr, dns_name);
+            AsyncClient::new(stream, sender, None)
+                .await
+                .map_err(|_| CheckFailure::Resolution)?
+        }
+        Transport::Https => {
+            let dns_name = check.name_server.tls_dns_name();
+            let stream = trust_dns_https::HttpsClientStreamBuilder::new()
+                .build::<TokioTcpStream>(socket_addr, dns_name);
+            AsyncClient::connect(stream)
+                .await
+                .map_err(|_| CheckFailure::Resolution)?
+        }
+    };
+
+    tokio::spawn(background);
+    Ok(client)
+}
+
+async fn perform_query_with_retries(
+    client: &mut AsyncClient,
+    check: &Check,
+    retries: usize,
+) -> Result<DnsResponse, CheckFailure> {
+    let res = client
+        .query(
+            check.record_to_request.clone(),
+            DNSClass::IN,
+            check.expected.record_type(),
+        )
+        .await;
+
+    match (res, retries) {
+        (Ok(res), _) => Ok(res),
+        (Err(_), 0) => Err(CheckFailure::Resolution),
+        (Err(_), retries) => Box::pin(perform_query_with_retries(client, check, retries - 1)).await,
+    }
+}
+
+fn perform_secure_query(
+    address: std::net::Ipv4Addr,
+    record_to_request: &trust_dns_client::rr::Name,
+    record_type: trust_dns_client::rr::RecordType,
+) -> Result<DnsResponse, CheckFailure> {
+    let socket_addr = SocketAddr::new(IpAddr::V4(address), 53);
+    let conn = UdpClientConnection::new(socket_addr).unwrap();
+    let client = SecureSyncClient::new(conn).build();
+    perform_secure_query_with_retries(client, record_to_request, record_type, RETRIES)
+}
+
+fn perform_secure_query_with_retries(
+    client: SecureSyncClient<UdpClientConnection>,
+    record_to_request: &trust_dns_client::rr::Name,
+    record_type: trust_dns_client::rr::RecordType,
+    retries: usize,
+) -> Result<DnsResponse, CheckFailure> {
+    let res = client.query(record_to_request, DNSClass::IN, record_type);
+
+    match (res, retries) {
+        (Ok(res), _) => Ok(res),
+        (Err(err), 0) => Err(classify_secure_failure(&err)),
+        (Err(_), retries) => {
+            perform_secure_query_with_retries(client, record_to_request, record_type, retries - 1)
+        }
+    }
+}
+
+/// Separates a genuine DNSSEC validation failure (bad or missing RRSIG/DS) from a
+/// plain transport failure (timeout, I/O error, no reachable connection) on the
+/// secure client, so the GitHub issue blames the right thing instead of reporting
+/// every dropped packet as a broken signing chain.
+fn classify_secure_failure(err: &trust_dns_client::error::ClientError) -> CheckFailure {
+    use trust_dns_client::error::ClientErrorKind;
+
+    match err.kind() {
+        ClientErrorKind::Timeout | ClientErrorKind::Io(_) | ClientErrorKind::NoConnections => {
+            CheckFailure::Resolution
+        }
+        _ => CheckFailure::Dnssec,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Checker;
+    use crate::{Check, CheckFailure};
+
+    /// Stands in for `DnsChecker` in tests that only care about how a set of check
+    /// results is handled, not about actually reaching the network.
+    struct MockChecker {
+        results: Vec<Result<(), CheckFailure>>,
+    }
+
+    impl Checker for MockChecker {
+        fn run_all(&self, _checks: &[Check]) -> Vec<Result<(), CheckFailure>> {
+            self.results
+                .iter()
+                .map(|result| match result {
+                    Ok(()) => Ok(()),
+                    Err(CheckFailure::Resolution) => Err(CheckFailure::Resolution),
+                    Err(CheckFailure::Mismatch) => Err(CheckFailure::Mismatch),
+                    Err(CheckFailure::Dnssec) => Err(CheckFailure::Dnssec),
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn mock_checker_returns_canned_results_in_order() {
+        let checker = MockChecker {
+            results: vec![Ok(()), Err(CheckFailure::Resolution)],
+        };
+
+        let results = checker.run_all(&[]);
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(CheckFailure::Resolution)));
+    }
+}
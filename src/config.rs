@@ -0,0 +1,142 @@
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use serde::Deserialize;
+use trust_dns_client::rr::Name;
+
+use crate::{Check, ExpectedRecord, NameServer, Transport};
+
+/// Top level config file shape. This replaces the previously hardcoded nameserver list
+/// and N*N check generation - add a host or zone here and its checks show up on the
+/// next run with no recompile.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub nameservers: Vec<NameServerConfig>,
+    pub checks: Vec<CheckSpec>,
+    /// Path to the sqlite database tracking check history and the open outage issue.
+    #[serde(default = "default_db_path")]
+    pub db_path: String,
+    /// Consecutive failing runs a check must accumulate before it counts toward an
+    /// outage. Suppresses a single transient timeout from opening/escalating an issue.
+    #[serde(default = "default_flap_threshold")]
+    pub flap_threshold: usize,
+    /// If the currently open outage issue hasn't been commented on in this many
+    /// seconds, open a fresh issue instead of bumping the stale one.
+    #[serde(default = "default_issue_stale_after_secs")]
+    pub issue_stale_after_secs: i64,
+    /// In `--daemon` mode, how long to sleep between sweeps while every check is
+    /// passing.
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// In `--daemon` mode, how long to sleep between sweeps while at least one check
+    /// is failing. Shorter than `check_interval_secs` so a real outage crosses
+    /// `flap_threshold` sooner instead of waiting out a full normal-health interval.
+    #[serde(default = "default_failing_recheck_interval_secs")]
+    pub failing_recheck_interval_secs: u64,
+}
+
+fn default_db_path() -> String {
+    "state.db".to_string()
+}
+
+fn default_flap_threshold() -> usize {
+    3
+}
+
+fn default_issue_stale_after_secs() -> i64 {
+    24 * 60 * 60
+}
+
+fn default_check_interval_secs() -> u64 {
+    60 * 60
+}
+
+fn default_failing_recheck_interval_secs() -> u64 {
+    10 * 60
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NameServerConfig {
+    pub name: String,
+    pub address: Ipv4Addr,
+    /// How checks against this nameserver are sent. Defaults to `udp` so existing
+    /// config files don't need updating.
+    #[serde(default)]
+    pub transport: Transport,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckSpec {
+    /// Must match a `NameServerConfig::name` above.
+    pub name_server: String,
+    pub record: String,
+    #[serde(flatten)]
+    pub expected: ExpectedRecordSpec,
+    /// Require DNSSEC chain-of-trust validation for this check. Defaults to `false` so
+    /// existing config files don't need updating.
+    #[serde(default)]
+    pub dnssec: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ExpectedRecordSpec {
+    A { value: Ipv4Addr },
+    Aaaa { value: Ipv6Addr },
+    Cname { value: String },
+    Mx { value: String },
+    Txt { value: String },
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Config, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    pub fn name_servers(&self) -> Vec<NameServer> {
+        self.nameservers
+            .iter()
+            .map(|ns| NameServer {
+                address: ns.address,
+                name: Name::from_str(&ns.name).unwrap(),
+                transport: ns.transport,
+            })
+            .collect()
+    }
+
+    /// Builds the checks described by this config against the given (already-parsed)
+    /// nameservers. `name_servers` must have been produced by `Config::name_servers` on
+    /// this same config, since each check's nameserver is looked up by name.
+    pub fn build_checks<'a>(&self, name_servers: &'a [NameServer]) -> Vec<Check<'a>> {
+        self.checks
+            .iter()
+            .map(|spec| {
+                let name_server = name_servers
+                    .iter()
+                    .find(|ns| ns.name == Name::from_str(&spec.name_server).unwrap())
+                    .unwrap_or_else(|| panic!("no nameserver configured named {}", spec.name_server));
+
+                let expected = match &spec.expected {
+                    ExpectedRecordSpec::A { value } => ExpectedRecord::A(*value),
+                    ExpectedRecordSpec::Aaaa { value } => ExpectedRecord::Aaaa(*value),
+                    ExpectedRecordSpec::Cname { value } => {
+                        ExpectedRecord::Cname(Name::from_str(value).unwrap())
+                    }
+                    ExpectedRecordSpec::Mx { value } => {
+                        ExpectedRecord::Mx(Name::from_str(value).unwrap())
+                    }
+                    ExpectedRecordSpec::Txt { value } => ExpectedRecord::Txt(value.clone()),
+                };
+
+                Check {
+                    name_server,
+                    record_to_request: Name::from_str(&spec.record).unwrap(),
+                    expected,
+                    dnssec: spec.dnssec,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,169 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS check_results (
+    id INTEGER PRIMARY KEY,
+    check_id TEXT NOT NULL,
+    passed BOOLEAN NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS check_results_check_id ON check_results (check_id);
+
+CREATE TABLE IF NOT EXISTS outage_issue (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    issue_number INTEGER NOT NULL,
+    last_comment_at INTEGER NOT NULL
+);
+";
+
+/// The GitHub outage issue we most recently opened or commented on, as tracked in our
+/// own state rather than re-derived from the GitHub API on every run.
+pub struct OutageIssue {
+    pub issue_number: u64,
+    pub last_comment_at: i64,
+}
+
+/// Sqlite-backed history of check results, used for flap suppression, plus a pointer
+/// to the currently open outage issue so `make_issue` doesn't need to re-list and
+/// re-parse issues from GitHub on every run.
+pub struct HistoryDb {
+    conn: Connection,
+}
+
+impl HistoryDb {
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn record_result(&self, check_id: &str, passed: bool, timestamp: i64) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO check_results (check_id, passed, timestamp) VALUES (?1, ?2, ?3)",
+            params![check_id, passed, timestamp],
+        )?;
+        Ok(())
+    }
+
+    /// Number of consecutive failing runs recorded for `check_id`, walking back from the
+    /// most recent result and stopping at the first pass (or the start of history).
+    pub fn consecutive_failures(&self, check_id: &str) -> rusqlite::Result<usize> {
+        let mut stmt = self.conn.prepare(
+            "SELECT passed FROM check_results WHERE check_id = ?1 ORDER BY timestamp DESC, id DESC",
+        )?;
+        let passed_rows = stmt.query_map(params![check_id], |row| row.get::<_, bool>(0))?;
+
+        let mut count = 0;
+        for passed in passed_rows {
+            if passed? {
+                break;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// This pointer is trusted as-is by `make_issue`/`resolve_issue` - it's never
+    /// reconciled against GitHub's actual list of open issues, so a lost/reset database
+    /// or a manually reopened issue won't be noticed. See the `TODO` on `make_issue`.
+    pub fn current_issue(&self) -> rusqlite::Result<Option<OutageIssue>> {
+        self.conn
+            .query_row(
+                "SELECT issue_number, last_comment_at FROM outage_issue WHERE id = 0",
+                [],
+                |row| {
+                    Ok(OutageIssue {
+                        issue_number: row.get(0)?,
+                        last_comment_at: row.get(1)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    pub fn set_current_issue(&self, issue_number: u64, last_comment_at: i64) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO outage_issue (id, issue_number, last_comment_at) VALUES (0, ?1, ?2)
+             ON CONFLICT (id) DO UPDATE SET issue_number = ?1, last_comment_at = ?2",
+            params![issue_number, last_comment_at],
+        )?;
+        Ok(())
+    }
+
+    /// Drops the current outage issue pointer once it has been closed, so the next
+    /// outage opens a fresh issue rather than reopening/commenting on the closed one.
+    pub fn clear_current_issue(&self) -> rusqlite::Result<()> {
+        self.conn.execute("DELETE FROM outage_issue WHERE id = 0", [])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HistoryDb;
+
+    #[test]
+    fn consecutive_failures_counts_back_to_the_last_pass() {
+        let db = HistoryDb::open(":memory:").unwrap();
+        db.record_result("check-a", true, 1).unwrap();
+        db.record_result("check-a", false, 2).unwrap();
+        db.record_result("check-a", false, 3).unwrap();
+        db.record_result("check-a", false, 4).unwrap();
+
+        assert_eq!(db.consecutive_failures("check-a").unwrap(), 3);
+    }
+
+    #[test]
+    fn consecutive_failures_resets_on_a_pass() {
+        let db = HistoryDb::open(":memory:").unwrap();
+        db.record_result("check-a", false, 1).unwrap();
+        db.record_result("check-a", false, 2).unwrap();
+        db.record_result("check-a", true, 3).unwrap();
+
+        assert_eq!(db.consecutive_failures("check-a").unwrap(), 0);
+    }
+
+    #[test]
+    fn consecutive_failures_is_zero_for_a_check_with_no_history() {
+        let db = HistoryDb::open(":memory:").unwrap();
+
+        assert_eq!(db.consecutive_failures("never-run").unwrap(), 0);
+    }
+
+    #[test]
+    fn consecutive_failures_is_tracked_independently_per_check() {
+        let db = HistoryDb::open(":memory:").unwrap();
+        db.record_result("check-a", false, 1).unwrap();
+        db.record_result("check-b", true, 1).unwrap();
+
+        assert_eq!(db.consecutive_failures("check-a").unwrap(), 1);
+        assert_eq!(db.consecutive_failures("check-b").unwrap(), 0);
+    }
+
+    #[test]
+    fn current_issue_is_none_until_one_is_set() {
+        let db = HistoryDb::open(":memory:").unwrap();
+
+        assert!(db.current_issue().unwrap().is_none());
+    }
+
+    #[test]
+    fn set_current_issue_bumps_last_comment_at_instead_of_inserting_a_second_row() {
+        let db = HistoryDb::open(":memory:").unwrap();
+        db.set_current_issue(42, 100).unwrap();
+        db.set_current_issue(42, 200).unwrap();
+
+        let issue = db.current_issue().unwrap().unwrap();
+        assert_eq!(issue.issue_number, 42);
+        assert_eq!(issue.last_comment_at, 200);
+    }
+
+    #[test]
+    fn clear_current_issue_forgets_it() {
+        let db = HistoryDb::open(":memory:").unwrap();
+        db.set_current_issue(42, 100).unwrap();
+        db.clear_current_issue().unwrap();
+
+        assert!(db.current_issue().unwrap().is_none());
+    }
+}
@@ -1,93 +1,323 @@
-use std::net::SocketAddr;
-use std::net::{IpAddr, Ipv4Addr};
+use std::collections::HashSet;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
-use trust_dns_client::client::{Client, SyncClient};
-use trust_dns_client::op::DnsResponse;
-use trust_dns_client::rr::{DNSClass, Name, RData, Record, RecordType};
-use trust_dns_client::udp::UdpClientConnection;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use tracing::{error, info, warn};
+use trust_dns_client::rr::{Name, RecordType};
+
+mod checker;
+mod config;
+mod history;
+
+use checker::{Checker, DnsChecker};
+use config::Config;
+use history::HistoryDb;
 
 struct NameServer {
     address: Ipv4Addr,
     name: Name,
+    transport: Transport,
+}
+
+impl NameServer {
+    /// Server name used for certificate validation on the `Tls`/`Https` transports.
+    /// Derived from the configured nameserver name with its trailing root dot trimmed,
+    /// since that dot isn't part of the name a certificate is issued for.
+    fn tls_dns_name(&self) -> String {
+        self.name.to_utf8().trim_end_matches('.').to_string()
+    }
+}
+
+/// How a check reaches its nameserver. Plaintext `Udp` is the historical default and
+/// `Tcp` is plaintext too; `Tls` (DoT) and `Https` (DoH) let a check verify that a
+/// resolver's encrypted endpoints are actually serving correct answers, not just its
+/// UDP listener.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Transport {
+    Udp,
+    Tcp,
+    Tls,
+    Https,
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Transport::Udp
+    }
+}
+
+impl Transport {
+    fn port(&self) -> u16 {
+        match self {
+            Transport::Udp | Transport::Tcp => 53,
+            Transport::Tls => 853,
+            Transport::Https => 443,
+        }
+    }
+}
+
+impl std::fmt::Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Transport::Udp => write!(f, "UDP"),
+            Transport::Tcp => write!(f, "TCP"),
+            Transport::Tls => write!(f, "DoT"),
+            Transport::Https => write!(f, "DoH"),
+        }
+    }
+}
+
+/// The record data a `Check` expects to find in the response. This covers the record
+/// types we actually see in our zones; add a variant here before adding a new kind of
+/// check.
+enum ExpectedRecord {
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    Cname(Name),
+    /// Exchange name of an MX record. We don't currently assert on preference.
+    Mx(Name),
+    /// A TXT check passes if any string in the record contains this substring.
+    Txt(String),
+}
+
+impl ExpectedRecord {
+    fn record_type(&self) -> RecordType {
+        match self {
+            ExpectedRecord::A(_) => RecordType::A,
+            ExpectedRecord::Aaaa(_) => RecordType::AAAA,
+            ExpectedRecord::Cname(_) => RecordType::CNAME,
+            ExpectedRecord::Mx(_) => RecordType::MX,
+            ExpectedRecord::Txt(_) => RecordType::TXT,
+        }
+    }
+}
+
+impl std::fmt::Display for ExpectedRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.record_type())
+    }
 }
 
-/// For now this represents an A record check, but it may become an enum or various
-/// check types (perhaps not limited to DNS checks).
 struct Check<'a> {
     name_server: &'a NameServer,
-    record_to_request: &'a Name,
-    expected_ip: &'a Ipv4Addr,
+    record_to_request: Name,
+    expected: ExpectedRecord,
+    /// When set, the query is issued through a `SecureSyncClient` and a failed
+    /// chain-of-trust validation counts as a failure on its own, separate from the
+    /// record actually matching `expected`. Always goes out over UDP regardless of the
+    /// nameserver's configured `transport` - the secure client has no DoT/DoH
+    /// counterpart yet.
+    dnssec: bool,
+}
+
+impl Check<'_> {
+    /// Stable identity for this check, used as the key for its history in the
+    /// `HistoryDb`. Two checks with the same nameserver, transport, record name, and
+    /// expected record type are considered the same check across runs - the same
+    /// record queried over `Udp` and `Tls` fails independently, so they get separate
+    /// history.
+    fn id(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.name_server.name,
+            self.name_server.transport,
+            self.record_to_request,
+            self.expected.record_type()
+        )
+    }
+}
+
+/// Why a check failed, so `format_check_results` can say something more useful than
+/// just FAIL.
+enum CheckFailure {
+    /// The query itself didn't come back (timeout, no answer, network error).
+    Resolution,
+    /// A response was received but didn't match `expected`.
+    Mismatch,
+    /// DNSSEC was requested for this check and the chain of trust didn't validate.
+    Dnssec,
+}
+
+impl std::fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CheckFailure::Resolution => write!(f, "FAIL (no response)"),
+            CheckFailure::Mismatch => write!(f, "FAIL (unexpected record)"),
+            CheckFailure::Dnssec => write!(f, "FAIL (DNSSEC validation failed)"),
+        }
+    }
 }
 
 const ISSUE_TITLE: &str = "Outage Report";
 
+const DAEMON_FLAG: &str = "--daemon";
+
+fn config_path() -> String {
+    std::env::args()
+        .skip(1)
+        .find(|arg| arg != DAEMON_FLAG)
+        .or_else(|| std::env::var("INFRA_TOOLS_CONFIG").ok())
+        .unwrap_or_else(|| "config.toml".to_string())
+}
+
+fn daemon_mode() -> bool {
+    std::env::args().any(|arg| arg == DAEMON_FLAG)
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_secs() as i64
+}
+
 fn main() {
-    let name_servers = get_name_servers();
-    let checks = get_checks(&name_servers);
-    let checks_with_results: Vec<(Check, Result<(), ()>)> = checks
-        .into_iter()
-        .map(|check| {
-            let result = perform_check(&check);
-            (check, result)
-        })
-        .collect();
-
-    let failed = checks_with_results
-        .iter()
-        .any(|(_check, result)| result.is_err());
-    if failed {
-        println!("Outage detected - creating GitHub issue");
-        // It may be worth hitting the github endpoint even if the tests pass, just
-        // to check that the access is still working. Perhaps even a weekly issue
-        // if the system is healthy would be useful to ensure the monitoring solution
-        // doesn't break.
-        make_issue(&checks_with_results).unwrap();
+    tracing_subscriber::fmt::init();
+
+    let config = Config::load(&config_path()).expect("failed to load config");
+    let history = HistoryDb::open(&config.db_path).expect("failed to open history db");
+    let checker = DnsChecker::new().expect("failed to start checker runtime");
+
+    if daemon_mode() {
+        run_daemon(&config, &history, &checker);
     } else {
-        println!("All checks completed. All services OK.");
+        run_checks(&config, &history, &checker);
     }
 }
 
-fn get_name_servers() -> Vec<NameServer> {
-    // Ideally we'd parse the ansible config for this information.
-    let ns1 = NameServer {
-        address: "173.255.245.83".parse().unwrap(),
-        name: Name::from_str("ns1.rhiyo.com.").unwrap(),
-    };
-    let ns2 = NameServer {
-        address: "212.71.246.209".parse().unwrap(),
-        name: Name::from_str("ns2.rhiyo.com.").unwrap(),
-    };
+/// Runs the monitor forever, sweeping checks on an interval rather than relying on an
+/// external cron/systemd timer. `currently_failing` tracks, across iterations, which
+/// checks failed on the most recent sweep - it drives the sleep interval (tighter while
+/// anything is failing, so an outage crosses `flap_threshold` sooner) and lets each
+/// cycle log failure/recovery transitions rather than just a flat pass/fail count.
+fn run_daemon(config: &Config, history: &HistoryDb, checker: &dyn Checker) -> ! {
+    let mut currently_failing: HashSet<String> = HashSet::new();
+
+    loop {
+        let failing_now = run_checks(config, history, checker);
 
-    vec![ns1, ns2]
+        for id in failing_now.difference(&currently_failing) {
+            warn!(check = %id, "check started failing");
+        }
+        for id in currently_failing.difference(&failing_now) {
+            info!(check = %id, "check recovered");
+        }
+        for id in failing_now.intersection(&currently_failing) {
+            warn!(check = %id, "check still failing");
+        }
+
+        currently_failing = failing_now;
+
+        let sleep_for = if currently_failing.is_empty() {
+            config.check_interval_secs
+        } else {
+            config.failing_recheck_interval_secs
+        };
+        info!(seconds = sleep_for, "sweep complete, sleeping");
+        std::thread::sleep(Duration::from_secs(sleep_for));
+    }
 }
 
-fn get_checks(name_servers: &Vec<NameServer>) -> Vec<Check> {
-    let mut checks = vec![];
+/// Runs a single sweep of `config`'s checks, updates `history`, and reconciles the
+/// GitHub outage issue (open/comment/close). Returns the ids of the checks that failed
+/// on this sweep, regardless of `flap_threshold` - the raw signal `run_daemon` uses to
+/// log transitions and choose its next sleep interval.
+///
+/// A failure reconciling the GitHub issue (rate limit, network blip, bad token) is
+/// logged rather than propagated - in `--daemon` mode that would otherwise crash the
+/// whole monitoring process over what's usually a transient API hiccup, leaving checks
+/// unmonitored until someone notices and restarts it by hand.
+fn run_checks(config: &Config, history: &HistoryDb, checker: &dyn Checker) -> HashSet<String> {
+    let name_servers = config.name_servers();
+    let checks = config.build_checks(&name_servers);
+    let results = checker.run_all(&checks);
+    let checks_with_results: Vec<(Check, Result<(), CheckFailure>)> =
+        checks.into_iter().zip(results).collect();
 
-    for name_server in name_servers {
-        // Right now the DNS servers are only configured with records for themselves. Again
-        // this information would ideally be parsed from the zonefile / ansible config
-        // so when additional zones or hosts are added new checks would automatically start.
-        for record_to_request in name_servers {
-            let check = Check {
-                name_server,
-                record_to_request: &record_to_request.name,
-                expected_ip: &record_to_request.address,
-            };
+    let now = now_unix();
+    let mut persistently_failing = false;
+    for (check, result) in &checks_with_results {
+        history
+            .record_result(&check.id(), result.is_ok(), now)
+            .expect("failed to record check result");
+        if result.is_err() {
+            let consecutive_failures = history
+                .consecutive_failures(&check.id())
+                .expect("failed to read check history");
+            // A single transient UDP timeout shouldn't spam GitHub - only count a check
+            // toward an outage once it has failed `flap_threshold` runs in a row.
+            if consecutive_failures >= config.flap_threshold {
+                persistently_failing = true;
+            }
+        }
+    }
+    let any_failing_now = checks_with_results.iter().any(|(_check, result)| result.is_err());
+    let open_issue_tracked = history
+        .current_issue()
+        .expect("failed to read outage issue state")
+        .is_some();
 
-            checks.push(check);
+    if persistently_failing {
+        info!("outage detected - creating GitHub issue");
+        // It may be worth hitting the github endpoint even if the tests pass, just
+        // to check that the access is still working. Perhaps even a weekly issue
+        // if the system is healthy would be useful to ensure the monitoring solution
+        // doesn't break.
+        if let Err(err) = make_issue(&checks_with_results, history, config) {
+            error!(%err, "failed to open/update GitHub outage issue");
+        }
+    } else if open_issue_tracked {
+        // Nothing is persistently failing any more, but an outage issue is still open
+        // from an earlier sweep - the underlying outage has recovered, so close it.
+        // This fires even if an unrelated check has a sub-threshold transient failure
+        // this sweep, since that alone never opened the issue in the first place - but
+        // `resolve_issue` still needs to know about it so it doesn't tell on-call every
+        // check is passing when one isn't.
+        info!("services recovered - resolving GitHub issue");
+        if let Err(err) = resolve_issue(&checks_with_results, any_failing_now, history) {
+            error!(%err, "failed to resolve GitHub outage issue");
         }
+    } else if any_failing_now {
+        info!("check(s) failing below flap_threshold, no action needed");
+    } else {
+        info!("all checks completed, all services OK");
     }
 
-    checks
+    checks_with_results
+        .iter()
+        .filter(|(_check, result)| result.is_err())
+        .map(|(check, _result)| check.id())
+        .collect()
+}
+
+/// Parses a GitHub issue number out of its API URL. The hubcaps `Issue` type doesn't
+/// expose the number directly, and it isn't the same as the issue's internal ID.
+fn issue_number_from_url(url: &str) -> u64 {
+    url.split("/").last().unwrap().parse().unwrap()
 }
 
-fn make_issue(checks: &Vec<(Check, Result<(), ()>)>) -> hubcaps::Result<()> {
-    use futures::stream::Stream;
+/// Opens a fresh outage issue, or comments on the one `history.current_issue()` is
+/// currently tracking if it hasn't gone stale.
+///
+/// TODO: `history` is the *only* source of truth for which issue is open - this never
+/// re-lists GitHub's own `ISSUE_TITLE` issues to reconcile with it. If `state.db` is
+/// ever lost or reset, or a human manually reopens an old outage issue, or the
+/// `issue_stale_after_secs` rollover above ever leaves two open at once, there's no way
+/// to notice here and this will happily open yet another one. Worth occasionally
+/// re-listing open issues titled `ISSUE_TITLE` and reconciling against the newest
+/// instead of trusting the DB pointer unconditionally.
+fn make_issue(
+    checks: &Vec<(Check, Result<(), CheckFailure>)>,
+    history: &HistoryDb,
+    config: &Config,
+) -> hubcaps::Result<()> {
     use tokio::runtime::Runtime;
 
     use hubcaps::comments::CommentOptions;
-    use hubcaps::issues::{Issue, IssueListOptions, IssueOptions, State};
+    use hubcaps::issues::IssueOptions;
     use hubcaps::{Credentials, Github};
     let github_api_key = dotenv::var("GITHUB_API_KEY").unwrap();
 
@@ -97,102 +327,121 @@ fn make_issue(checks: &Vec<(Check, Result<(), ()>)>) -> hubcaps::Result<()> {
         Credentials::Token(github_api_key),
     );
     let repo = github.repo("joshmcguigan", "infra");
-    let existing_outage_issues: Vec<Issue> = rt.block_on(
-        repo.issues()
-            .iter(
-                &IssueListOptions::builder()
-                    .per_page(100)
-                    .state(State::Open)
-                    .build(),
-            )
-            .filter(|issue| issue.title.contains(ISSUE_TITLE))
-            .collect(),
-    )?;
 
-    // If there is more than one currently open outage issue, this takes the first. Perhaps
-    // it would be better to take the newest.
-    //
-    // For now, there should only ever be at most one open outage issue unless one is
-    // manually closed, then an issue happens triggering automatic issue creation, then
-    // the older issue is manually re-opened.
-    //
-    // It might be nice to have some "timeout" for open outage issues, so that if some time
-    // has past since the last comment in an outage issue a new issue is created rather than
-    // bumping the existing issue.
-    match existing_outage_issues.first() {
+    let now = now_unix();
+    let existing_issue = history
+        .current_issue()
+        .expect("failed to read outage issue state")
+        .filter(|issue| now - issue.last_comment_at < config.issue_stale_after_secs);
+
+    match existing_issue {
         Some(existing_issue) => {
-            // Unfortunately the API does not seem to have a nice way to get issue number, so
-            // it must be parsed from the issue URL. Note issue number is not the same as
-            // issue ID.
-            let issue_number: u64 = existing_issue
-                .url
-                .split("/")
-                .last()
-                .unwrap()
-                .parse()
-                .unwrap();
-            rt.block_on(repo.issue(issue_number).comments().create(&CommentOptions {
-                body: format_check_results(&checks),
-            }))?;
+            rt.block_on(
+                repo.issue(existing_issue.issue_number)
+                    .comments()
+                    .create(&CommentOptions {
+                        body: format_check_results(&checks),
+                    }),
+            )?;
+            history
+                .set_current_issue(existing_issue.issue_number, now)
+                .expect("failed to update outage issue state");
         }
         None => {
-            // Create new outage issue
-            rt.block_on(repo.issues().create(&IssueOptions::new(
+            // No open issue, or the last one has gone quiet for longer than
+            // `issue_stale_after_secs` - start a fresh one rather than bumping a stale
+            // issue that may no longer reflect the current outage.
+            let created = rt.block_on(repo.issues().create(&IssueOptions::new(
                 ISSUE_TITLE,
                 Some(format_check_results(&checks)),
                 Option::<String>::None,
                 None,
                 Vec::<String>::new(),
             )))?;
+            history
+                .set_current_issue(issue_number_from_url(&created.url), now)
+                .expect("failed to save outage issue state");
         }
     }
 
     Ok(())
 }
 
-fn perform_check(check: &Check) -> Result<(), ()> {
-    let socket_addr = SocketAddr::new(IpAddr::V4(check.name_server.address), 53);
-    let conn = UdpClientConnection::new(socket_addr).unwrap();
-    let client = SyncClient::new(conn);
+/// Posts a recovery comment on the open outage issue and closes it, then forgets it in
+/// `history` so the next outage opens a fresh issue. Does nothing if `history` is not
+/// tracking an outage issue; an issue closed by hand on GitHub is still tracked, since
+/// the pointer is never checked against GitHub.
+///
+/// `any_failing_now` reflects whether a check is failing below `flap_threshold` this
+/// sweep - the outage itself has still recovered (nothing persistently failing), but
+/// the comment needs to say so honestly rather than claiming a clean all-clear while a
+/// check is actually down.
+fn resolve_issue(
+    checks: &Vec<(Check, Result<(), CheckFailure>)>,
+    any_failing_now: bool,
+    history: &HistoryDb,
+) -> hubcaps::Result<()> {
+    use tokio::runtime::Runtime;
 
-    let retries = 2;
-    let response = perform_query_with_retries(client, check, retries)?;
-    let answers: &[Record] = response.answers();
+    use hubcaps::comments::CommentOptions;
+    use hubcaps::issues::{IssueEditOptions, State};
+    use hubcaps::{Credentials, Github};
 
-    if let RData::A(ref ip) = answers[0].rdata() {
-        if ip == check.expected_ip {
-            Ok(())
-        } else {
-            Err(())
-        }
+    let issue = match history
+        .current_issue()
+        .expect("failed to read outage issue state")
+    {
+        Some(issue) => issue,
+        None => return Ok(()),
+    };
+
+    let body = if any_failing_now {
+        format!(
+            "Outage resolved - no check has failed enough consecutive runs to count \
+             toward it any more. One or more checks are still failing below \
+             flap_threshold this sweep:\n\n{}",
+            format_check_results(checks)
+        )
     } else {
-        Err(())
-    }
-}
+        "Services recovered - all checks are passing again.".to_string()
+    };
+
+    let github_api_key = dotenv::var("GITHUB_API_KEY").unwrap();
+    let mut rt = Runtime::new()?;
+    let github = Github::new(
+        concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")),
+        Credentials::Token(github_api_key),
+    );
+    let repo = github.repo("joshmcguigan", "infra");
 
-fn perform_query_with_retries(
-    client: SyncClient<UdpClientConnection>,
-    check: &Check,
-    retries: usize,
-) -> Result<DnsResponse, ()> {
-    let res = client.query(&check.record_to_request, DNSClass::IN, RecordType::A);
+    rt.block_on(repo.issue(issue.issue_number).comments().create(&CommentOptions { body }))?;
+    rt.block_on(
+        repo.issue(issue.issue_number)
+            .edit(&IssueEditOptions::builder().state(State::Closed).build()),
+    )?;
 
-    match (res, retries) {
-        (Ok(res), _) => Ok(res),
-        (Err(_), 0) => Err(()),
-        (Err(_), retries) => perform_query_with_retries(client, check, retries - 1),
-    }
+    history
+        .clear_current_issue()
+        .expect("failed to clear outage issue state");
+
+    Ok(())
 }
 
-fn format_check_results(checks: &Vec<(Check, Result<(), ()>)>) -> String {
+fn format_check_results(checks: &Vec<(Check, Result<(), CheckFailure>)>) -> String {
     let mut s = String::from("Automated outage report\n\n");
 
     for (check, result) in checks {
+        let status = match result {
+            Ok(()) => "PASS".to_string(),
+            Err(failure) => failure.to_string(),
+        };
         s += &format!(
-            "Server {} resolving {} {}\n",
+            "Server {} [{}] resolving {} ({}) {}\n",
             check.name_server.name,
+            check.name_server.transport,
             check.record_to_request,
-            if result.is_ok() { "PASS" } else { "FAIL" },
+            check.expected,
+            status,
         );
     }
 
@@ -201,20 +450,60 @@ fn format_check_results(checks: &Vec<(Check, Result<(), ()>)>) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{format_check_results, get_checks, get_name_servers, Check, Name};
+    use super::{format_check_results, Check, CheckFailure, Config, Name, Transport};
     use std::str::FromStr;
 
+    fn test_config() -> Config {
+        toml::from_str(
+            r#"
+            [[nameservers]]
+            name = "ns1.rhiyo.com."
+            address = "173.255.245.83"
+
+            [[nameservers]]
+            name = "ns2.rhiyo.com."
+            address = "212.71.246.209"
+
+            [[checks]]
+            name_server = "ns1.rhiyo.com."
+            record = "ns1.rhiyo.com."
+            type = "a"
+            value = "173.255.245.83"
+
+            [[checks]]
+            name_server = "ns1.rhiyo.com."
+            record = "ns2.rhiyo.com."
+            type = "a"
+            value = "212.71.246.209"
+
+            [[checks]]
+            name_server = "ns2.rhiyo.com."
+            record = "ns1.rhiyo.com."
+            type = "a"
+            value = "173.255.245.83"
+
+            [[checks]]
+            name_server = "ns2.rhiyo.com."
+            record = "ns2.rhiyo.com."
+            type = "a"
+            value = "212.71.246.209"
+            "#,
+        )
+        .unwrap()
+    }
+
     #[test]
     fn format() {
-        let name_servers = get_name_servers();
-        let checks = get_checks(&name_servers);
-        let checks_with_results: Vec<(Check, Result<(), ()>)> = checks
+        let config = test_config();
+        let name_servers = config.name_servers();
+        let checks = config.build_checks(&name_servers);
+        let checks_with_results: Vec<(Check, Result<(), CheckFailure>)> = checks
             .into_iter()
             .map(|check| {
                 // simulate failure of NS2
                 let ns2 = Name::from_str("ns2.rhiyo.com.").unwrap();
                 let result = if check.name_server.name == ns2 {
-                    Err(())
+                    Err(CheckFailure::Mismatch)
                 } else {
                     Ok(())
                 };
@@ -224,6 +513,42 @@ mod tests {
 
         let output_string = format_check_results(&checks_with_results);
 
-        assert_eq!("Automated outage report\n\nServer ns1.rhiyo.com. resolving ns1.rhiyo.com. PASS\nServer ns1.rhiyo.com. resolving ns2.rhiyo.com. PASS\nServer ns2.rhiyo.com. resolving ns1.rhiyo.com. FAIL\nServer ns2.rhiyo.com. resolving ns2.rhiyo.com. FAIL\n", output_string);
+        assert_eq!("Automated outage report\n\nServer ns1.rhiyo.com. [UDP] resolving ns1.rhiyo.com. (A) PASS\nServer ns1.rhiyo.com. [UDP] resolving ns2.rhiyo.com. (A) PASS\nServer ns2.rhiyo.com. [UDP] resolving ns1.rhiyo.com. (A) FAIL (unexpected record)\nServer ns2.rhiyo.com. [UDP] resolving ns2.rhiyo.com. (A) FAIL (unexpected record)\n", output_string);
+    }
+
+    #[test]
+    fn transport_and_dnssec_are_read_from_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [[nameservers]]
+            name = "ns1.rhiyo.com."
+            address = "173.255.245.83"
+            transport = "tls"
+
+            [[checks]]
+            name_server = "ns1.rhiyo.com."
+            record = "ns1.rhiyo.com."
+            type = "a"
+            value = "173.255.245.83"
+            dnssec = true
+            "#,
+        )
+        .unwrap();
+
+        let name_servers = config.name_servers();
+        let checks = config.build_checks(&name_servers);
+
+        assert_eq!(checks[0].name_server.transport, Transport::Tls);
+        assert!(checks[0].dnssec);
+    }
+
+    #[test]
+    fn transport_and_dnssec_default_when_omitted() {
+        let config = test_config();
+        let name_servers = config.name_servers();
+        let checks = config.build_checks(&name_servers);
+
+        assert_eq!(checks[0].name_server.transport, Transport::Udp);
+        assert!(!checks[0].dnssec);
     }
 }